@@ -0,0 +1,93 @@
+use nalgebra::Vector3;
+
+use super::observer_pos::{pvobs, ObserverError, Ut1Source};
+
+/// Speed of light, in astronomical units per day
+const VLIGHT_AU_DAY: f64 = 173.144632674240;
+
+/// Number of light-time iterations; two or three passes converge to sub-milliarcsecond accuracy
+const LIGHT_TIME_ITERATIONS: usize = 3;
+
+/// Apparent direction of a target once light-time and stellar aberration are accounted for
+pub struct ApparentDirection {
+    /// Unit vector from the observer to the target, corrected for light-time and aberration (ecliptic J2000)
+    pub direction: Vector3<f64>,
+    /// Converged light-time, in days
+    pub light_time: f64,
+}
+
+/// Correct a target direction for light-time and stellar aberration
+///
+/// Iterates the light-time equation `τ = |ρ|/c`, re-evaluating the target position at `t − τ`
+/// against the observer position from [pvobs] for a fixed number of passes
+/// (`LIGHT_TIME_ITERATIONS`), with no convergence test, then adds the stellar
+/// aberration due to the observer velocity and renormalizes.
+///
+/// Argument
+/// --------
+/// * tmjd: time of the observation in modified julian date (MJD)
+/// * longitude: observer longitude on Earth in degree
+/// * latitude: observer latitude on Earth in degree
+/// * height: observer height on Earth in meter
+/// * target_position: closure returning the target geocentric position (ecliptic J2000, AU) at a given MJD
+/// * ut1_source: source of the UTC-to-UT1 correction, see [Ut1Source]
+///
+/// Return
+/// ------
+/// * the apparent direction to the target and the converged light-time, see [ApparentDirection]
+pub fn correct_light_time_and_aberration(
+    tmjd: f64,
+    longitude: f64,
+    latitude: f64,
+    height: f64,
+    target_position: impl Fn(f64) -> Vector3<f64>,
+    ut1_source: &dyn Ut1Source,
+) -> Result<ApparentDirection, ObserverError> {
+    let (observer_position, observer_velocity) =
+        pvobs(tmjd, longitude, latitude, height, ut1_source)?;
+
+    let mut light_time = 0.;
+    let mut rho = Vector3::zeros();
+    for _ in 0..LIGHT_TIME_ITERATIONS {
+        let target_pos = target_position(tmjd - light_time);
+        rho = target_pos - observer_position;
+        light_time = rho.norm() / VLIGHT_AU_DAY;
+    }
+
+    let aberrated = rho.normalize() + observer_velocity / VLIGHT_AU_DAY;
+
+    Ok(ApparentDirection {
+        direction: aberrated.normalize(),
+        light_time,
+    })
+}
+
+#[cfg(test)]
+mod light_time_tests {
+
+    use super::super::observer_pos::NoUt1Correction;
+    use super::*;
+
+    #[test]
+    fn correct_light_time_and_aberration_test() {
+        let tmjd = 57028.479297592596;
+        // longitude, latitude and height of Pan-STARRS 1, Haleakala
+        let (lon, lat, h) = (203.744090000, 20.707233557, 3067.694);
+
+        // target at a fixed geocentric position, independent of epoch
+        let target_position = |_tmjd: f64| Vector3::new(1., 0., 0.);
+
+        // NoUt1Correction pins this test to a fixed tut = tmjd, so it runs offline and
+        // deterministically instead of depending on a live JPL download.
+        let apparent =
+            correct_light_time_and_aberration(tmjd, lon, lat, h, target_position, &NoUt1Correction)
+                .unwrap();
+
+        // The observer is about one Earth radius (~4.3e-5 AU) away from the geocenter, so the
+        // light-time to a 1 AU target stays within that bound of 1 AU / c, regardless of the
+        // (tut-dependent) orientation of the Earth at the observation epoch.
+        assert!((apparent.light_time - 1. / VLIGHT_AU_DAY).abs() < 1e-6);
+        assert!((apparent.direction.norm() - 1.).abs() < 1e-12);
+        assert!(apparent.direction.x > 0.999);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+use super::super::constants::ERAU;
+use super::observer_pos::{pvobs_from_fixed_coord, ObserverError, Ut1Source};
+
+/// MPC parallax constants for a single observatory, as published in the `ObsCodes` table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObservatoryCode {
+    /// Observatory longitude, in degree
+    pub longitude: f64,
+    /// `rho * cos(phi)`, the normalized radius of the observatory projected on the equatorial plane
+    pub rho_cos_phi: f64,
+    /// `rho * sin(phi)`, the normalized radius of the observatory projected on the polar axis
+    pub rho_sin_phi: f64,
+}
+
+/// Lookup table of MPC observatory codes, keyed by their three-character code
+///
+/// Built from the `ObsCodes` listing published by the Minor Planet Center, so users can
+/// refer to an observatory as "F51" or "807" instead of hand-entering its coordinates.
+#[derive(Debug, Clone, Default)]
+pub struct ObsCodeTable {
+    codes: HashMap<String, ObservatoryCode>,
+}
+
+impl ObsCodeTable {
+    /// Parse an MPC `ObsCodes` table
+    ///
+    /// Each data line is expected to start with the three-character observatory code,
+    /// followed by whitespace-separated longitude, `rho*cos(phi)` and `rho*sin(phi)`
+    /// columns. Header lines and entries missing the parallax constants (e.g. spacecraft)
+    /// are skipped.
+    ///
+    /// Argument
+    /// --------
+    /// * content: the raw contents of the `ObsCodes` table
+    ///
+    /// Return
+    /// ------
+    /// * the parsed lookup table
+    pub fn parse(content: &str) -> Self {
+        let mut codes = HashMap::new();
+
+        for line in content.lines() {
+            if line.len() < 4 {
+                continue;
+            }
+            let code = &line[0..3];
+            let mut fields = line[3..].split_whitespace();
+
+            let longitude = fields.next().and_then(|v| v.parse::<f64>().ok());
+            let rho_cos_phi = fields.next().and_then(|v| v.parse::<f64>().ok());
+            let rho_sin_phi = fields.next().and_then(|v| v.parse::<f64>().ok());
+
+            if let (Some(longitude), Some(rho_cos_phi), Some(rho_sin_phi)) =
+                (longitude, rho_cos_phi, rho_sin_phi)
+            {
+                codes.insert(
+                    code.to_string(),
+                    ObservatoryCode {
+                        longitude,
+                        rho_cos_phi,
+                        rho_sin_phi,
+                    },
+                );
+            }
+        }
+
+        ObsCodeTable { codes }
+    }
+
+    /// Look up an observatory by its three-character MPC code
+    pub fn get(&self, code: &str) -> Option<&ObservatoryCode> {
+        self.codes.get(code)
+    }
+}
+
+/// Get the fixed position of an observatory using its MPC observatory code
+///
+/// Plugs the parallax constants published by the MPC directly into the body-fixed vector
+/// construction, bypassing `geodetic_to_parallax`.
+///
+/// Argument
+/// --------
+/// * table: the parsed MPC observatory code table, see [ObsCodeTable]
+/// * code: three-character MPC observatory code, e.g. "F51" or "807"
+///
+/// Return
+/// ------
+/// * observer fixed coordinates vector on the Earth (not corrected from Earth motion), in AU,
+///   or `None` if `code` is not in `table`
+pub fn body_fixed_coord_from_code(table: &ObsCodeTable, code: &str) -> Option<Vector3<f64>> {
+    let obs = table.get(code)?;
+    let lon_radians = obs.longitude.to_radians();
+
+    Some(Vector3::new(
+        ERAU * obs.rho_cos_phi * lon_radians.cos(),
+        ERAU * obs.rho_cos_phi * lon_radians.sin(),
+        ERAU * obs.rho_sin_phi,
+    ))
+}
+
+/// Get the observer position and velocity on the Earth from its MPC observatory code
+///
+/// Argument
+/// --------
+/// * table: the parsed MPC observatory code table, see [ObsCodeTable]
+/// * code: three-character MPC observatory code, e.g. "F51" or "807"
+/// * tmjd: time of the observation in modified julian date (MJD)
+/// * ut1_source: source of the UTC-to-UT1 correction, see [Ut1Source]
+///
+/// Return
+/// ------
+/// * dx: corrected observer position with respect to the center of mass of Earth (in ecliptic J2000)
+/// * dy: corrected observer velocity with respect to the center of mass of Earth (in ecliptic J2000)
+pub fn pvobs_from_code(
+    table: &ObsCodeTable,
+    code: &str,
+    tmjd: f64,
+    ut1_source: &dyn Ut1Source,
+) -> Result<(Vector3<f64>, Vector3<f64>), ObserverError> {
+    let dxbf = body_fixed_coord_from_code(table, code)
+        .ok_or_else(|| ObserverError::UnknownObservatoryCode(code.to_string()))?;
+    pvobs_from_fixed_coord(tmjd, dxbf, ut1_source)
+}
+
+#[cfg(test)]
+mod mpc_obscodes_tests {
+
+    use super::*;
+
+    const SAMPLE_OBSCODES: &str = "\
+Code  Long.   cos      sin    Name
+000   0.0000 0.62411  0.77873 Greenwich
+F51 203.744090 0.936241 0.351543 Pan-STARRS 1, Haleakala
+249               Hipparcos Space Observatory
+";
+
+    #[test]
+    fn parse_test() {
+        let table = ObsCodeTable::parse(SAMPLE_OBSCODES);
+
+        assert_eq!(
+            table.get("000"),
+            Some(&ObservatoryCode {
+                longitude: 0.0,
+                rho_cos_phi: 0.62411,
+                rho_sin_phi: 0.77873,
+            })
+        );
+        assert!(table.get("249").is_none());
+        assert!(table.get("XXX").is_none());
+    }
+
+    #[test]
+    fn body_fixed_coord_from_code_test() {
+        let table = ObsCodeTable::parse(SAMPLE_OBSCODES);
+
+        let obs_fixed_vector = body_fixed_coord_from_code(&table, "F51").unwrap();
+        assert_eq!(
+            obs_fixed_vector,
+            Vector3::new(
+                -0.00003653799438523036,
+                -0.000016072603969775588,
+                0.000014988110491809297
+            )
+        )
+    }
+
+    #[test]
+    fn body_fixed_coord_from_code_missing_test() {
+        let table = ObsCodeTable::parse(SAMPLE_OBSCODES);
+        assert!(body_fixed_coord_from_code(&table, "XXX").is_none());
+    }
+}
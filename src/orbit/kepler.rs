@@ -0,0 +1,160 @@
+use nalgebra::Vector3;
+
+use super::super::constants::DPI;
+
+/// Number of bisection halvings used to solve Kepler's equation; about 50 gives double precision
+const KEPLER_ITERATIONS: usize = 53;
+
+/// Classical (Keplerian) orbital elements at a given epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitalElements {
+    /// Semi-major axis, in AU
+    pub semi_major_axis: f64,
+    /// Eccentricity
+    pub eccentricity: f64,
+    /// Inclination, in degree
+    pub inclination: f64,
+    /// Longitude of the ascending node, in degree
+    pub ascending_node: f64,
+    /// Argument of perihelion, in degree
+    pub argument_of_perihelion: f64,
+    /// Mean anomaly at epoch, in degree
+    pub mean_anomaly: f64,
+}
+
+/// Orbital state resulting from propagating [OrbitalElements] to their epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitState {
+    /// True anomaly, in radians
+    pub true_anomaly: f64,
+    /// Radius vector length, in AU
+    pub radius: f64,
+    /// Position, in the ecliptic J2000 frame, in AU
+    pub position: Vector3<f64>,
+}
+
+/// Solve Kepler's equation `M = E - e*sin(E)` for the eccentric anomaly `E`
+///
+/// Uses a bisection search that is globally convergent for all eccentricities, rather than
+/// a Newton iteration that can diverge close to `e = 1`: reduce `M` into `[0, 2π)`, start with
+/// `E = π` and step `D = π/2` so the search spans the full `[0, 2π)` range, then repeatedly
+/// move `E` by `D` towards the root and halve `D`.
+///
+/// Argument
+/// --------
+/// * mean_anomaly: mean anomaly `M`, in radians
+/// * eccentricity: eccentricity `e`
+///
+/// Return
+/// ------
+/// * the eccentric anomaly `E`, in radians
+fn solve_kepler_equation(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let m = mean_anomaly.rem_euclid(DPI);
+
+    let mut e = std::f64::consts::PI;
+    let mut d = std::f64::consts::FRAC_PI_2;
+
+    for _ in 0..KEPLER_ITERATIONS {
+        let m1 = e - eccentricity * e.sin();
+        e += d * (m - m1).signum();
+        d /= 2.;
+    }
+
+    e
+}
+
+/// Propagate classical orbital elements to their state at epoch
+///
+/// Solves Kepler's equation for the eccentric anomaly, derives the true anomaly and radius,
+/// then rotates the orbital-plane position by the argument of perihelion, inclination and
+/// longitude of the ascending node into the ecliptic J2000 frame, so the result plugs
+/// directly into [super::super::jpl_request::light_time::correct_light_time_and_aberration]
+/// or [super::super::jpl_request::observer_pos::topocentric_observation].
+///
+/// Argument
+/// --------
+/// * elements: the classical orbital elements, see [OrbitalElements]
+///
+/// Return
+/// ------
+/// * the true anomaly, radius and ecliptic J2000 position, see [OrbitState]
+pub fn propagate(elements: &OrbitalElements) -> OrbitState {
+    let eccentricity = elements.eccentricity;
+    let mean_anomaly = elements.mean_anomaly.to_radians();
+
+    let eccentric_anomaly = solve_kepler_equation(mean_anomaly, eccentricity);
+
+    let radius = elements.semi_major_axis * (1. - eccentricity * eccentric_anomaly.cos());
+    let true_anomaly = 2. * ((1. + eccentricity).sqrt() * (eccentric_anomaly / 2.).sin())
+        .atan2((1. - eccentricity).sqrt() * (eccentric_anomaly / 2.).cos());
+
+    // Position in the orbital plane
+    let x_orb = radius * true_anomaly.cos();
+    let y_orb = radius * true_anomaly.sin();
+
+    // Rotate the orbital plane into the ecliptic J2000 frame
+    let (sin_node, cos_node) = elements.ascending_node.to_radians().sin_cos();
+    let (sin_peri, cos_peri) = elements.argument_of_perihelion.to_radians().sin_cos();
+    let (sin_incl, cos_incl) = elements.inclination.to_radians().sin_cos();
+
+    let x = (cos_node * cos_peri - sin_node * sin_peri * cos_incl) * x_orb
+        + (-cos_node * sin_peri - sin_node * cos_peri * cos_incl) * y_orb;
+    let y = (sin_node * cos_peri + cos_node * sin_peri * cos_incl) * x_orb
+        + (-sin_node * sin_peri + cos_node * cos_peri * cos_incl) * y_orb;
+    let z = (sin_peri * sin_incl) * x_orb + (cos_peri * sin_incl) * y_orb;
+
+    OrbitState {
+        true_anomaly,
+        radius,
+        position: Vector3::new(x, y, z),
+    }
+}
+
+#[cfg(test)]
+mod kepler_tests {
+
+    use super::*;
+
+    #[test]
+    fn solve_kepler_equation_test() {
+        let eccentric_anomaly = solve_kepler_equation(30_f64.to_radians(), 0.2);
+        assert_eq!(eccentric_anomaly, 0.6436173778335978);
+    }
+
+    #[test]
+    fn propagate_test() {
+        let elements = OrbitalElements {
+            semi_major_axis: 2.5,
+            eccentricity: 0.2,
+            inclination: 10.,
+            ascending_node: 80.,
+            argument_of_perihelion: 40.,
+            mean_anomaly: 30.,
+        };
+
+        let state = propagate(&elements);
+
+        assert_eq!(state.radius, 2.1000348834157134);
+        assert_eq!(state.true_anomaly, 0.7753289911467004);
+        assert_eq!(
+            state.position.as_slice(),
+            [-1.9916314731799194, 0.5584123270594978, 0.362941124137275]
+        );
+    }
+
+    #[test]
+    fn solve_kepler_equation_high_eccentricity_test() {
+        // near-parabolic orbit: the bisection search must still converge
+        let eccentric_anomaly = solve_kepler_equation(10_f64.to_radians(), 0.99);
+        let mean_anomaly = eccentric_anomaly - 0.99 * eccentric_anomaly.sin();
+        assert!((mean_anomaly - 10_f64.to_radians()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn solve_kepler_equation_past_half_orbit_test() {
+        // M > 180 degrees: the search must span the full [0, 2pi) range, not just [0, pi]
+        let eccentric_anomaly = solve_kepler_equation(270_f64.to_radians(), 0.2);
+        let mean_anomaly = eccentric_anomaly - 0.2 * eccentric_anomaly.sin();
+        assert!((mean_anomaly - 270_f64.to_radians()).abs() < 1e-9);
+    }
+}